@@ -1,9 +1,11 @@
 use macroquad::prelude::*;
 
 use ron::de::from_reader;
-use serde::Deserialize;
+use ron::ser::{to_writer_pretty, PrettyConfig};
+use serde::{Deserialize, Serialize};
 
 use std::fs::File;
+use std::io;
 
 
 pub const CONFIG_NAME: &str = "config.ron";
@@ -13,7 +15,7 @@ pub fn debug_key_held() -> bool {
     is_key_down(KeyCode::LeftShift) || is_key_down(KeyCode::RightShift)
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct GameConfig {
     /// The number of pixels to play with.
     pub num_pixels: usize,
@@ -26,6 +28,22 @@ pub struct GameConfig {
 
     /// Debug config
     pub dbg: DebugConfig,
+
+    /// Neural-network agent config
+    pub agent: AgentConfig,
+
+    /// Fixed arena dimensions, used instead of the window size so the
+    /// simulation stays identical regardless of screen/window size.
+    pub arena: ArenaConfig,
+
+    /// Netplay config
+    pub netplay: NetplayConfig,
+
+    /// Gravity-field scripting config
+    pub scripting: ScriptingConfig,
+
+    /// Gamepad config
+    pub gamepad: GamepadConfig,
 }
 
 impl GameConfig {
@@ -44,12 +62,73 @@ impl GameConfig {
         assert!(config.phy.friction       <  config.phy.acceleration);
         assert!(config.gfx.min_brightness <= config.gfx.max_brightness);
         assert!(config.gfx.pixel_size     >  0.);
+        assert!(config.agent.survivors    <= config.agent.population_size);
+        assert!(config.agent.mut_rate     >= 0. && config.agent.mut_rate <= 1.);
+        assert!(config.arena.width        >  0.);
+        assert!(config.arena.height       >  0.);
+        assert!(config.phy.tick_rate      >  0.);
+        assert!(config.gfx.star_z_min     >  0.);
+        assert!(config.gfx.star_z_min     <= config.gfx.star_z_max);
+        assert!(config.gfx.star_size_min  <= config.gfx.star_size_max);
+        assert!(config.gamepad.cursor_speed >= 0.);
+        assert!(!config.netplay.enabled || config.netplay.peer_addrs.len()
+                == config.netplay.num_players - 1);
 
         config
     }
+
+    /// Serializes this config back to RON and writes it to `filename`,
+    /// e.g. so the live editor can persist in-game tweaks.
+    pub fn write_config(&self, filename: &str) -> io::Result<()> {
+        let file = File::create(filename)?;
+        to_writer_pretty(file, self, PrettyConfig::default())
+            .map_err(|e| io::Error::new(io::ErrorKind::Other, e))
+    }
+
+    /// Largest value `friction` can take while still satisfying `friction <
+    /// acceleration` in `f32` arithmetic. `f32::EPSILON` alone is only a real
+    /// gap near magnitude `1.0`; for larger `acceleration` it rounds straight
+    /// back to `acceleration`, so the step is scaled to `acceleration`'s own
+    /// magnitude instead.
+    pub fn max_friction(acceleration: f32) -> f32 {
+        let step = (acceleration.abs() * f32::EPSILON).max(f32::MIN_POSITIVE);
+        (acceleration - step).max(0.)
+    }
+
+    /// Clamps every field so the invariants checked in `read_config` hold,
+    /// instead of panicking. Used by the live editor, which edits the
+    /// running config field-by-field and can't afford to crash the sandbox
+    /// over a single out-of-range slider. The netplay peer-count check is not
+    /// clamped.
+    pub fn clamp_invariants(&mut self) {
+        self.phy.max_velocity = self.phy.max_velocity.max(0.);
+        self.phy.friction     = self.phy.friction.max(0.);
+        // Must stay strictly positive: at `acceleration == 0`,
+        // `max_friction(0.)` is also `0.`, which would clamp `friction` down
+        // to `0.` too and leave `friction < acceleration` violated.
+        self.phy.acceleration = self.phy.acceleration.max(f32::EPSILON);
+        self.phy.friction     = self.phy.friction.min(Self::max_friction(self.phy.acceleration));
+        self.phy.tick_rate = self.phy.tick_rate.max(f32::EPSILON);
+
+        if self.gfx.min_brightness > self.gfx.max_brightness {
+            self.gfx.min_brightness = self.gfx.max_brightness;
+        }
+        self.gfx.pixel_size = self.gfx.pixel_size.max(f32::EPSILON);
+        self.gfx.star_z_min    = self.gfx.star_z_min.max(f32::EPSILON);
+        self.gfx.star_z_max    = self.gfx.star_z_max.max(self.gfx.star_z_min);
+        self.gfx.star_size_max = self.gfx.star_size_max.max(self.gfx.star_size_min);
+
+        self.agent.survivors = self.agent.survivors.min(self.agent.population_size);
+        self.agent.mut_rate  = self.agent.mut_rate.clamp(0., 1.);
+
+        self.arena.width  = self.arena.width.max(1.);
+        self.arena.height = self.arena.height.max(1.);
+
+        self.gamepad.cursor_speed = self.gamepad.cursor_speed.max(0.);
+    }
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct PhysicsConfig {
     /// Maximum velocity of the pixels
     pub max_velocity: f32,
@@ -63,9 +142,14 @@ pub struct PhysicsConfig {
 
     /// Area of effect of gravity fields
     pub gravity_field_aoe: f32,
+
+    /// Fixed physics timestep, in seconds. The simulation advances by this
+    /// much per tick regardless of the render framerate, so replays and
+    /// netplay stay deterministic.
+    pub tick_rate: f32,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct GraphicsConfig {
     /// Size of a single pixel
     pub pixel_size: f32,
@@ -75,9 +159,30 @@ pub struct GraphicsConfig {
 
     /// Maximal brightness level of the pixels
     pub max_brightness: u8,
+
+    /// Number of background stars in the parallax starfield.
+    pub star_count: usize,
+
+    /// Minimal depth of a background star. Lower depths render bigger,
+    /// brighter, and shift more under parallax.
+    pub star_z_min: f32,
+
+    /// Maximal depth of a background star.
+    pub star_z_max: f32,
+
+    /// Minimal base size of a background star, before depth scaling.
+    pub star_size_min: f32,
+
+    /// Maximal base size of a background star, before depth scaling.
+    pub star_size_max: f32,
+
+    /// Whether active pixels are also assigned a random depth (drawn from
+    /// `[star_z_min, star_z_max]`) that scales their `pixel_size`, instead
+    /// of always rendering at depth `1.0`.
+    pub pixel_depth_enabled: bool,
 }
 
-#[derive(Deserialize)]
+#[derive(Serialize, Deserialize)]
 pub struct DebugConfig {
     /// Whether to automatically show debug info on pause
     pub on_pause: bool,
@@ -91,3 +196,75 @@ pub struct DebugConfig {
     /// Whether to show the current number of fields in the arena
     pub n_fields: bool,
 }
+
+#[derive(Serialize, Deserialize)]
+pub struct AgentConfig {
+    /// Whether pixels are steered by an evolving neural network instead of
+    /// the summed gravity-field forces.
+    pub enabled: bool,
+
+    /// Number of brains kept in the population.
+    pub population_size: usize,
+
+    /// Sizes of the hidden layers between the (fixed) input and (fixed,
+    /// 2-wide) output layer.
+    pub hidden_layers: Vec<usize>,
+
+    /// Per-weight probability of mutation when breeding a new generation.
+    pub mut_rate: f32,
+
+    /// Number of top-scoring brains that survive each generation unchanged.
+    pub survivors: usize,
+
+    /// Number of physics ticks a generation lives before being scored and
+    /// replaced.
+    pub generation_ticks: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ArenaConfig {
+    /// Fixed width of the arena, independent of the window's width.
+    pub width: f32,
+
+    /// Fixed height of the arena, independent of the window's height.
+    pub height: f32,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct NetplayConfig {
+    /// Whether to exchange inputs with remote peers instead of running the
+    /// arena purely locally.
+    pub enabled: bool,
+
+    /// Total number of players sharing this arena, including the local one.
+    pub num_players: usize,
+
+    /// Number of ticks local input is delayed by before being applied, to
+    /// give remote input time to arrive before a rollback is needed.
+    pub input_delay: u32,
+
+    /// Local UDP port to listen on.
+    pub local_port: u16,
+
+    /// `ip:port` address of every other peer.
+    pub peer_addrs: Vec<String>,
+
+    /// Shared PRNG seed. Every peer must configure the same value so the
+    /// simulation's `Rng` starts bit-identical everywhere and stays that way
+    /// under deterministic replay.
+    pub seed: u64,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct ScriptingConfig {
+    /// Maps a `GravityField::behavior` name to its Rhai source, compiled
+    /// once at config load by `ScriptEngine`.
+    pub behaviors: std::collections::HashMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct GamepadConfig {
+    /// How many arena units per second the virtual cursor moves at full
+    /// stick deflection.
+    pub cursor_speed: f32,
+}
@@ -0,0 +1,77 @@
+use egui_macroquad::egui;
+
+use crate::config::{GameConfig, CONFIG_NAME};
+
+/// Runtime overlay that exposes every field of `PhysicsConfig` and
+/// `DebugConfig`, plus the pixel size and brightness bounds of
+/// `GraphicsConfig`, as live-editable widgets, so the sim can be tuned
+/// without editing `config.ron` and hitting Escape. The starfield and
+/// pixel-depth fields of `GraphicsConfig` are not exposed.
+pub struct Editor {
+    pub open: bool,
+}
+
+impl Editor {
+    pub fn new() -> Self {
+        Self { open: false }
+    }
+
+    /// Draws the overlay (if open) and applies edits straight into `config`,
+    /// re-validating its invariants afterwards so the UI can't drive the
+    /// sandbox into an invalid state.
+    pub fn show(&mut self, config: &mut GameConfig) {
+        if !self.open {
+            return;
+        }
+
+        egui_macroquad::ui(|ctx| {
+            egui::Window::new("Pixen Editor").show(ctx, |ui| {
+                ui.heading("Physics");
+                ui.add(egui::Slider::new(&mut config.phy.friction,
+                                          0.0..=GameConfig::max_friction(config.phy.acceleration))
+                                          .text("friction"));
+                ui.add(egui::Slider::new(&mut config.phy.acceleration,
+                                          f32::EPSILON..=20.0).text("acceleration"));
+                ui.add(egui::Slider::new(&mut config.phy.max_velocity,
+                                          0.0..=50.0).text("max_velocity"));
+                ui.add(egui::Slider::new(&mut config.phy.gravity_field_aoe,
+                                          0.0..=1000.0).text("gravity_field_aoe"));
+                ui.add(egui::Slider::new(&mut config.phy.tick_rate,
+                                          f32::EPSILON..=0.1).text("tick_rate"));
+
+                ui.separator();
+                ui.heading("Graphics");
+                ui.add(egui::Slider::new(&mut config.gfx.pixel_size,
+                                          0.5..=20.0).text("pixel_size"));
+                ui.add(egui::Slider::new(&mut config.gfx.min_brightness,
+                                          0..=255).text("min_brightness"));
+                ui.add(egui::Slider::new(&mut config.gfx.max_brightness,
+                                          0..=255).text("max_brightness"));
+
+                ui.separator();
+                ui.heading("Debug");
+                ui.checkbox(&mut config.dbg.on_pause, "on_pause");
+                ui.checkbox(&mut config.dbg.fps, "fps");
+                ui.checkbox(&mut config.dbg.draw_fields, "draw_fields");
+                ui.checkbox(&mut config.dbg.n_fields, "n_fields");
+
+                ui.separator();
+                if ui.button("Save to RON").clicked() {
+                    if let Err(e) = config.write_config(CONFIG_NAME) {
+                        eprintln!("Couldn't save the configuration file: {e}");
+                    }
+                }
+            });
+        });
+
+        config.clamp_invariants();
+    }
+
+    /// Paints the overlay built up by `show` onto the screen. Call after the
+    /// rest of the frame has been drawn, so the editor renders on top.
+    pub fn draw(&self) {
+        if self.open {
+            egui_macroquad::draw();
+        }
+    }
+}
@@ -0,0 +1,82 @@
+//! Gamepad input, read through `quad_gamepad`'s controller API so the arena
+//! is playable without a mouse. Hot-plugging is supported: the active
+//! controller is re-picked whenever the current one disconnects.
+
+use quad_gamepad::{ControllerContext, ControllerButton, GamepadId};
+
+use crate::vector::Vector;
+
+/// This frame's gamepad state, already edge-detected so `GameField` doesn't
+/// need to track "was this held last frame" itself.
+pub struct GamepadState {
+    /// Left stick deflection, each axis in `[-1.0, 1.0]`.
+    pub stick: Vector,
+
+    /// Face button that spawns an attracting gravity field, just pressed.
+    pub attract_pressed: bool,
+
+    /// Face button that spawns a repelling gravity field, just pressed.
+    pub repel_pressed: bool,
+
+    /// Face button that removes the first field found under the cursor (the
+    /// same lookup as MMB), just pressed.
+    pub remove_pressed: bool,
+
+    /// Start button, just pressed. Toggles pause.
+    pub start_pressed: bool,
+}
+
+/// Tracks the currently active gamepad and the button state needed to
+/// detect presses (as opposed to "currently held").
+pub struct GamepadInput {
+    ctx: ControllerContext,
+    active: Option<GamepadId>,
+    prev_buttons: [bool; 4],
+}
+
+impl GamepadInput {
+    pub fn new() -> Self {
+        Self {
+            ctx: ControllerContext::new(),
+            active: None,
+            prev_buttons: [false; 4],
+        }
+    }
+
+    /// Polls every connected controller, picking up hot-plugged ones and
+    /// dropping the active one if it disconnected, and returns its state.
+    /// Returns `None` if no gamepad is connected.
+    pub fn poll(&mut self) -> Option<GamepadState> {
+        self.ctx.update();
+
+        let still_connected = self.active
+            .is_some_and(|id| self.ctx.is_connected(id));
+        if !still_connected {
+            self.active = (0..quad_gamepad::MAX_DEVICES)
+                .find(|&id| self.ctx.is_connected(id));
+            self.prev_buttons = [false; 4];
+        }
+
+        let id    = self.active?;
+        let state = self.ctx.state(id);
+
+        let buttons = [
+            state.digital_state[ControllerButton::A as usize],
+            state.digital_state[ControllerButton::B as usize],
+            state.digital_state[ControllerButton::X as usize],
+            state.digital_state[ControllerButton::Start as usize],
+        ];
+        let pressed: Vec<bool> = buttons.iter().zip(self.prev_buttons.iter())
+            .map(|(&now, &before)| now && !before)
+            .collect();
+        self.prev_buttons = buttons;
+
+        Some(GamepadState {
+            stick:           Vector::new(state.analog_state[0], state.analog_state[1]),
+            attract_pressed: pressed[0],
+            repel_pressed:   pressed[1],
+            remove_pressed:  pressed[2],
+            start_pressed:   pressed[3],
+        })
+    }
+}
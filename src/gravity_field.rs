@@ -1,5 +1,6 @@
 use crate::vector::*;
 
+#[derive(Clone)]
 /// A gravity field in the arena.
 /// The field can either attract or repel.
 pub struct GravityField {
@@ -13,18 +14,30 @@ pub struct GravityField {
     /// Gravitational strength applied to pixels affected by this field.
     /// The higher the strength, the faster the affected pixels accelerate.
     pub strength: f32,
+
+    /// Name of the Rhai behavior script driving this field's force model,
+    /// looked up in `ScriptEngine`. `None` uses the default constant-strength
+    /// inverse-direction pull.
+    pub behavior: Option<String>,
 }
 
 impl GravityField {
-    /// Spawns a new gravity field
+    /// Spawns a new gravity field with the default force model
     pub fn new(position: Vector, aoe: f32, strength: f32) -> Self {
         Self {
             position,
             aoe,
             strength,
+            behavior: None,
         }
     }
 
+    /// Sets the named Rhai behavior driving this field's force model
+    pub fn with_behavior(mut self, behavior: impl Into<String>) -> Self {
+        self.behavior = Some(behavior.into());
+        self
+    }
+
     /// Checks whether a vector is inside the `aoe` of this field
     pub fn in_aoe(&self, vector: &Vector) -> bool {
         self.position.distance(vector) <= self.aoe
@@ -0,0 +1,11 @@
+pub mod vector;
+pub mod rng;
+pub mod pixel;
+pub mod gravity_field;
+pub mod config;
+pub mod nn;
+pub mod netplay;
+pub mod editor;
+pub mod scripting;
+pub mod starfield;
+pub mod gamepad;
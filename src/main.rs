@@ -5,8 +5,22 @@ use pixen::vector::*;
 use pixen::rng::*;
 use pixen::pixel::*;
 use pixen::gravity_field::*;
+use pixen::nn::*;
+use pixen::netplay::*;
+use pixen::editor::Editor;
+use pixen::scripting::ScriptEngine;
+use pixen::starfield::Starfield;
+use pixen::gamepad::GamepadInput;
 
 
+/// Number of values fed into an agent brain: the normalized direction and
+/// distance to the nearest two `GravityField`s (3 values each), plus the
+/// pixel's own velocity (2 values).
+const NN_INPUTS: usize = 3 * 2 + 2;
+
+/// A brain outputs a steering (ax, ay) vector.
+const NN_OUTPUTS: usize = 2;
+
 /// The game field which is used for the game
 struct GameField {
     /// Pixels in the arena
@@ -15,14 +29,66 @@ struct GameField {
     /// Gravity fields in the arena
     gravity_fields: Vec<GravityField>,
 
-    /// The game RNG
+    /// The game RNG. Advanced only by deterministic per-tick logic and
+    /// included in every `netplay::Snapshot`, so it must never be touched by
+    /// anything framerate-dependent (see `render_rng`).
     rng: Rng,
 
+    /// Cosmetic per-frame RNG used only for render-time flourishes (e.g.
+    /// pixel brightness). Kept separate from `rng` because `render` runs
+    /// once per render frame rather than once per fixed tick, so drawing
+    /// from `rng` here would make the snapshotted RNG state depend on the
+    /// framerate instead of `tick_count`.
+    render_rng: Rng,
+
     /// Whether the game is paused
     is_paused: bool,
 
     /// The game configuration
     config: GameConfig,
+
+    /// The evolving pool of pixel brains, present when `config.agent.enabled`.
+    population: Option<Population>,
+
+    /// Physics ticks elapsed in the current generation.
+    generation_tick: u64,
+
+    /// Leftover render-frame time not yet consumed by a fixed physics tick.
+    accumulator: f32,
+
+    /// Number of fixed-timestep physics ticks simulated so far. Used as the
+    /// shared clock for `netplay` rollback.
+    tick_count: u64,
+
+    /// The live config-tuning overlay.
+    editor: Editor,
+
+    /// Compiled Rhai scripts backing named `GravityField` behaviors.
+    scripts: ScriptEngine,
+
+    /// Parallax background stars, rendered behind the pixels.
+    starfield: Starfield,
+
+    /// Gamepad reader, tracking which controller is currently active.
+    gamepad: GamepadInput,
+
+    /// Virtual cursor moved by the active gamepad's left stick. Mirrors
+    /// what the mouse cursor drives when no gamepad is active.
+    cursor: Vector,
+
+    /// Whether a gamepad is currently driving the virtual cursor.
+    gamepad_active: bool,
+
+    /// Whether `netplay` is driving this field. While set, `handle_input`
+    /// must not mutate shared sim state directly: local actions instead
+    /// accumulate into `pending_input` and flow through the delayed/buffered
+    /// path in `netplay_tick`, so they're applied exactly once, in sync with
+    /// the rest of the simulation.
+    netplay_enabled: bool,
+
+    /// Local presses accumulated since the last tick consumed them, drained
+    /// and sent by `netplay_tick`. Only used while `netplay_enabled`.
+    pending_input: Input,
 }
 
 impl GameField {
@@ -30,31 +96,84 @@ impl GameField {
     /// and populating the `pixels` vec with them.
     fn new(config: GameConfig) -> Self {
         // Create the struct fields
+        let scripts = ScriptEngine::new(&config.scripting.behaviors);
+        let mut rng = if config.netplay.enabled {
+            Rng::seeded(config.netplay.seed)
+        } else {
+            Rng::new()
+        };
+        let starfield = Starfield::new(
+            config.gfx.star_count,
+            config.gfx.star_z_min, config.gfx.star_z_max,
+            config.gfx.star_size_min, config.gfx.star_size_max,
+            config.arena.width, config.arena.height,
+            &mut rng,
+        );
+
         let mut temp = Self {
-            pixels:         vec![],
-            gravity_fields: vec![],
-            rng:            Rng::new(),
-            is_paused:      false,
+            pixels:          vec![],
+            gravity_fields:  vec![],
+            rng,
+            render_rng:      Rng::new(),
+            is_paused:       false,
+            population:      None,
+            generation_tick: 0,
+            accumulator:     0.,
+            tick_count:      0,
+            editor:          Editor::new(),
+            scripts,
+            starfield,
+            gamepad:        GamepadInput::new(),
+            cursor:         Vector::new(config.arena.width / 2., config.arena.height / 2.),
+            gamepad_active: false,
+            netplay_enabled: config.netplay.enabled,
+            pending_input:   Input::default(),
             config,
         };
+        if temp.config.agent.enabled {
+            let mut layers = vec![NN_INPUTS];
+            layers.extend_from_slice(&temp.config.agent.hidden_layers);
+            layers.push(NN_OUTPUTS);
+
+            temp.population = Some(Population::new(
+                temp.config.agent.population_size,
+                layers,
+                ActivFunc::Relu,
+                temp.config.agent.mut_rate,
+                &mut temp.rng,
+            ));
+        }
         temp.populate_pixels();
         temp
     }
 
     /// Populates the inner `pixels` vector with the amount of pixels defined
-    /// by config.
+    /// by config. If agent mode is enabled, pixels are handed out brains from
+    /// the population round-robin.
     fn populate_pixels(&mut self) {
         self.pixels = Vec::with_capacity(self.config.num_pixels);
 
         // Spawn the pixels and put them inside the `pixels` vec
-        for _ in 0..self.config.num_pixels {
-            let pos_x = (self.rng.rand() % screen_width()  as u64) as f32;
-            let pos_y = (self.rng.rand() % screen_height() as u64) as f32;
-            self.pixels.push(Pixel::new(Vector::new(pos_x, pos_y)));
+        for i in 0..self.config.num_pixels {
+            let pos_x = (self.rng.rand() % self.config.arena.width  as u64) as f32;
+            let pos_y = (self.rng.rand() % self.config.arena.height as u64) as f32;
+            let pos   = Vector::new(pos_x, pos_y);
+
+            let mut px = match &self.population {
+                Some(pop) => Pixel::with_brain(pos, i % pop.brains.len()),
+                None      => Pixel::new(pos),
+            };
+            if self.config.gfx.pixel_depth_enabled {
+                let t = self.rng.range(0, 1000) as f32 / 1000.;
+                px = px.with_depth(self.config.gfx.star_z_min
+                    + t * (self.config.gfx.star_z_max - self.config.gfx.star_z_min));
+            }
+            self.pixels.push(px);
         }
     }
 
-    /// Updates the game state and ticks the physics engine once.
+    /// Handles real-time input once per render frame, independent of the
+    /// fixed-timestep physics in `tick`.
     ///
     /// * Escape resets the arena
     /// * Space pauses the arena (new gravity fields can still be spawned).
@@ -62,41 +181,120 @@ impl GameField {
     /// * LMB press creates an attracting gravity field,
     /// * RMB press creates a repelling gravity field.
     /// * MMB press removes the first placed gravity field under the cursor.
-    fn update(&mut self) {
+    fn handle_input(&mut self) {
         let mouse_pos = Vector::coords(mouse_position());
 
-        // LMB press creates an attracting gravity field,
-        if is_mouse_button_pressed(MouseButton::Left) {
-            self.gravity_fields.push(
-                GravityField::new(
-                    mouse_pos,
-                    self.config.phy.gravity_field_aoe,
-                    self.config.phy.acceleration,
-                )
-            );
-        // RMB press creates a repelling gravity field.
-        } else if is_mouse_button_pressed(MouseButton::Right) {
-            self.gravity_fields.push(
-                GravityField::new(
-                    mouse_pos,
-                    self.config.phy.gravity_field_aoe,
-                    -self.config.phy.acceleration,
-                )
-            );
-        // MMB press removes the first placed gravity field under the cursor.
-        } else if is_mouse_button_pressed(MouseButton::Middle) {
-            let field = self.gravity_fields.iter().enumerate().find(|(_, e)| {
-                e.position.distance(&mouse_pos) <= 10.
-            });
-            if let Some((idx, _)) = field {
-                self.gravity_fields.swap_remove(idx);
+        if self.netplay_enabled {
+            // Under netplay, local actions must flow through the delayed/
+            // buffered path in `netplay_tick` only, so they're applied
+            // exactly once and in sync with remote peers. Accumulate
+            // presses here instead of mutating the arena directly;
+            // `netplay_tick` drains `pending_input` once per tick.
+            self.pending_input.mouse_pos = mouse_pos;
+            self.pending_input.lmb   |= is_mouse_button_pressed(MouseButton::Left);
+            self.pending_input.rmb   |= is_mouse_button_pressed(MouseButton::Right);
+            self.pending_input.mmb   |= is_mouse_button_pressed(MouseButton::Middle);
+            self.pending_input.space |= is_key_pressed(KeyCode::Space);
+        } else {
+            // LMB press creates an attracting gravity field,
+            if is_mouse_button_pressed(MouseButton::Left) {
+                self.gravity_fields.push(
+                    GravityField::new(
+                        mouse_pos,
+                        self.config.phy.gravity_field_aoe,
+                        self.config.phy.acceleration,
+                    )
+                );
+            // RMB press creates a repelling gravity field.
+            } else if is_mouse_button_pressed(MouseButton::Right) {
+                self.gravity_fields.push(
+                    GravityField::new(
+                        mouse_pos,
+                        self.config.phy.gravity_field_aoe,
+                        -self.config.phy.acceleration,
+                    )
+                );
+            // MMB press removes the first placed gravity field under the cursor.
+            } else if is_mouse_button_pressed(MouseButton::Middle) {
+                let field = self.gravity_fields.iter().enumerate().find(|(_, e)| {
+                    e.position.distance(&mouse_pos) <= 10.
+                });
+                if let Some((idx, _)) = field {
+                    self.gravity_fields.swap_remove(idx);
+                }
+            }
+
+            // Space pauses the arena
+            if is_key_pressed(KeyCode::Space) {
+                self.is_paused = !self.is_paused;
+            }
+        }
+
+        // Gamepad: left stick moves the virtual cursor. Mouse/keyboard keep
+        // working alongside it. The face buttons/Start mutate shared sim
+        // state directly, same as the mouse/keyboard above, so they're only
+        // wired up outside netplay: gamepad input isn't exchanged with
+        // peers, and there's no buffered path for it to flow through.
+        self.gamepad_active = false;
+        if let Some(gp) = self.gamepad.poll() {
+            self.gamepad_active = true;
+
+            self.cursor += gp.stick * Vector::from(
+                self.config.gamepad.cursor_speed * get_frame_time());
+            self.cursor.x = self.cursor.x.clamp(0., self.config.arena.width);
+            self.cursor.y = self.cursor.y.clamp(0., self.config.arena.height);
+
+            if !self.netplay_enabled {
+                if gp.attract_pressed {
+                    self.gravity_fields.push(GravityField::new(
+                        self.cursor, self.config.phy.gravity_field_aoe,
+                        self.config.phy.acceleration));
+                } else if gp.repel_pressed {
+                    self.gravity_fields.push(GravityField::new(
+                        self.cursor, self.config.phy.gravity_field_aoe,
+                        -self.config.phy.acceleration));
+                } else if gp.remove_pressed {
+                    let field = self.gravity_fields.iter().enumerate().find(|(_, e)| {
+                        e.position.distance(&self.cursor) <= 10.
+                    });
+                    if let Some((idx, _)) = field {
+                        self.gravity_fields.swap_remove(idx);
+                    }
+                }
+
+                if gp.start_pressed {
+                    self.is_paused = !self.is_paused;
+                }
             }
         }
 
         // Escape resets the arena.
         // If shift is held, gravity fields won't be cleared.
         if is_key_pressed(KeyCode::Escape) {
-            self.config = GameConfig::read_config(CONFIG_NAME);
+            self.config    = GameConfig::read_config(CONFIG_NAME);
+            self.netplay_enabled = self.config.netplay.enabled;
+            if self.netplay_enabled {
+                self.rng = Rng::seeded(self.config.netplay.seed);
+            }
+            self.scripts   = ScriptEngine::new(&self.config.scripting.behaviors);
+            self.starfield = Starfield::new(
+                self.config.gfx.star_count,
+                self.config.gfx.star_z_min, self.config.gfx.star_z_max,
+                self.config.gfx.star_size_min, self.config.gfx.star_size_max,
+                self.config.arena.width, self.config.arena.height,
+                &mut self.rng,
+            );
+            if !self.config.agent.enabled {
+                self.population = None;
+            } else if self.population.is_none() {
+                let mut layers = vec![NN_INPUTS];
+                layers.extend_from_slice(&self.config.agent.hidden_layers);
+                layers.push(NN_OUTPUTS);
+                self.population = Some(Population::new(
+                    self.config.agent.population_size, layers, ActivFunc::Relu,
+                    self.config.agent.mut_rate, &mut self.rng));
+                self.generation_tick = 0;
+            }
             self.populate_pixels();
             if !is_key_down(KeyCode::LeftShift) &&
                     !is_key_down(KeyCode::RightShift) {
@@ -104,10 +302,22 @@ impl GameField {
             }
         }
 
-        // Space pauses the arena
-        if is_key_pressed(KeyCode::Space) {
-            self.is_paused = !self.is_paused;
+        // F1 toggles the live editor overlay
+        if is_key_pressed(KeyCode::F1) {
+            self.editor.open = !self.editor.open;
         }
+    }
+
+    /// Ticks the physics engine once, by a fixed timestep. Decoupled from
+    /// `handle_input` and the render framerate so the simulation stays
+    /// deterministic (needed for `netplay` rollback and replays).
+    fn tick(&mut self) {
+        // `tick_count` is the netplay clock and must advance every tick,
+        // paused or not, so that pausing doesn't stall `advance_tick`'s
+        // input lookups and make a buffered Space toggle pause on and off
+        // on repeat.
+        self.tick_count += 1;
+
         if self.is_paused {
             return;
         }
@@ -117,14 +327,38 @@ impl GameField {
         for px in self.pixels.iter_mut() {
             // Calculate the direction and acceleration of this pixel
             acceleration.clear();
-            for field in &self.gravity_fields {
-                if !field.in_aoe(&px.position) {
-                    continue;
+            match (px.brain, &self.population) {
+                // Agent mode: the pixel's brain steers it instead of the
+                // summed gravity-field forces.
+                (Some(brain), Some(population)) => {
+                    let inputs = nearest_fields_input(&px.position, &px.velocity,
+                                                       &self.gravity_fields);
+                    let out = population.brains[brain].forward(&inputs);
+                    acceleration = Vector::new(out[0], out[1]);
+
+                    // Reward time spent inside an attracting field.
+                    if self.gravity_fields.iter()
+                        .any(|f| f.strength > 0. && f.in_aoe(&px.position))
+                    {
+                        px.fitness += 1.;
+                    }
+                }
+                // Default mode: sum the forces of every field in range, as
+                // computed by each field's behavior script (or the default
+                // constant-strength pull, if it has none).
+                _ => {
+                    for field in &self.gravity_fields {
+                        if !field.in_aoe(&px.position) {
+                            continue;
+                        }
+
+                        let relative = field.position - px.position;
+                        let distance = field.position.distance(&px.position);
+                        acceleration += self.scripts.force(
+                            field.behavior.as_deref(), relative, distance,
+                            field.strength, field.aoe);
+                    }
                 }
-
-                let mut direction = field.position - px.position;
-                direction.normalize();
-                acceleration += direction * Vector::from(field.strength);
             }
 
             // Create friction - inverted and normalized velocity.
@@ -142,21 +376,96 @@ impl GameField {
             px.velocity.limit(self.config.phy.max_velocity);
             px.position += px.velocity;
         }
+
+        // Advance the generation once every pixel's brain has had enough
+        // ticks to prove itself, then breed a new one from the fittest.
+        if self.population.is_some() {
+            self.generation_tick += 1;
+            if self.generation_tick >= self.config.agent.generation_ticks {
+                self.evolve_population();
+                self.generation_tick = 0;
+            }
+        }
+    }
+
+    /// Scores every brain by the fitness its pixel(s) accumulated this
+    /// generation, breeds the next generation, and resets pixel fitness.
+    fn evolve_population(&mut self) {
+        let Some(population) = &mut self.population else { return };
+
+        let mut fitness = vec![0.; population.brains.len()];
+        for px in self.pixels.iter_mut() {
+            if let Some(brain) = px.brain {
+                fitness[brain] += px.fitness;
+                px.fitness = 0.;
+            }
+        }
+
+        population.evolve(&fitness, self.config.agent.survivors, &mut self.rng);
+    }
+
+    /// Captures the tick, pixels, gravity fields, RNG and pause state as a
+    /// `netplay::Snapshot`. The agent population is not captured.
+    fn snapshot(&self) -> Snapshot {
+        Snapshot {
+            tick:           self.tick_count,
+            pixels:         self.pixels.clone(),
+            gravity_fields: self.gravity_fields.clone(),
+            rng:            self.rng.clone(),
+            is_paused:      self.is_paused,
+        }
+    }
+
+    /// Restores a previously captured `netplay::Snapshot`, e.g. to roll back
+    /// from a misprediction.
+    fn restore(&mut self, snapshot: &Snapshot) {
+        self.tick_count     = snapshot.tick;
+        self.pixels         = snapshot.pixels.clone();
+        self.gravity_fields = snapshot.gravity_fields.clone();
+        self.rng            = snapshot.rng.clone();
+        self.is_paused      = snapshot.is_paused;
+    }
+
+    /// Applies a single peer's `netplay::Input` the same way `handle_input`
+    /// applies the local mouse/keyboard, so remote and replayed input drive
+    /// the simulation identically.
+    fn apply_remote_action(&mut self, input: Input) {
+        if input.lmb {
+            self.gravity_fields.push(GravityField::new(
+                input.mouse_pos, self.config.phy.gravity_field_aoe,
+                self.config.phy.acceleration));
+        } else if input.rmb {
+            self.gravity_fields.push(GravityField::new(
+                input.mouse_pos, self.config.phy.gravity_field_aoe,
+                -self.config.phy.acceleration));
+        } else if input.mmb {
+            let field = self.gravity_fields.iter().enumerate().find(|(_, e)| {
+                e.position.distance(&input.mouse_pos) <= 10.
+            });
+            if let Some((idx, _)) = field {
+                self.gravity_fields.swap_remove(idx);
+            }
+        }
+
+        if input.space {
+            self.is_paused = !self.is_paused;
+        }
     }
 
     #[allow(dead_code)]
     /// Keeps the pixels on the screen (within its bounds, that is width/height)
     fn keep_within_bounds(&mut self) {
+        let (width, height) = (self.config.arena.width, self.config.arena.height);
         for px in self.pixels.iter_mut() {
-            if px.position.x > screen_width() || px.position.x < 0. {
+            if px.position.x > width || px.position.x < 0. {
                 px.velocity.x *= -1.;
             }
-            if px.position.y > screen_height() || px.position.y < 0. {
+            if px.position.y > height || px.position.y < 0. {
                 px.velocity.y *= -1.;
             }
 
-            px.position.x = px.position.x.clamp(0., screen_width());
-            px.position.y = px.position.y.clamp(0., screen_height());
+            px.position.x = px.position.x.clamp(0., width);
+            px.position.y = px.position.y.clamp(0., height);
         }
     }
 
@@ -164,17 +473,18 @@ impl GameField {
     /// When pixels reach one edge, their location is set to the other.
     /// This behavior is equal to the one in Snake (when the arena is unbounded)
     fn snake_bounds(&mut self) {
+        let (width, height) = (self.config.arena.width, self.config.arena.height);
         for px in self.pixels.iter_mut() {
-            if px.position.x > screen_width() {
+            if px.position.x > width {
                 px.position.x = 0.;
             } else if px.position.x < 0. {
-                px.position.x = screen_width();
+                px.position.x = width;
             }
 
-            if px.position.y > screen_height() {
+            if px.position.y > height {
                 px.position.y = 0.;
             } else if px.position.y < 0. {
-                px.position.y = screen_height();
+                px.position.y = height;
             }
         }
     }
@@ -183,17 +493,25 @@ impl GameField {
     fn render(&mut self) {
         clear_background(BLACK);
 
+        // Draw the parallax starfield behind everything else. The view
+        // shifts with the cursor, since the arena has no real camera.
+        let arena_center = Vector::new(self.config.arena.width  / 2.,
+                                        self.config.arena.height / 2.);
+        let view_offset  = (Vector::coords(mouse_position()) - arena_center)
+            * Vector::from(0.05);
+        self.starfield.render(view_offset);
+
         // Draw pixels
         for px in self.pixels.iter() {
             // Pixels have a random brightness every frame
-            let px_color = self.rng.range(
+            let px_color = self.render_rng.range(
                 self.config.gfx.min_brightness as u64,
                 self.config.gfx.max_brightness as u64
             ) as u8;
             let px_color = Color::from_rgba(px_color, px_color, px_color, 255);
 
             draw_circle(px.position.x, px.position.y,
-                        self.config.gfx.pixel_size, px_color);
+                        self.config.gfx.pixel_size / px.depth, px_color);
         }
 
         // Draw debug info
@@ -236,7 +554,84 @@ impl GameField {
                 }
             }
         }
+
+        // Draw the virtual cursor while a gamepad is driving it
+        if self.gamepad_active {
+            draw_circle_lines(self.cursor.x, self.cursor.y, 8., 2., YELLOW);
+        }
+
+        // Draw the live editor overlay, if open
+        self.editor.show(&mut self.config);
+        self.editor.draw();
+    }
+}
+
+/// Builds the fixed input vector fed to an agent brain: the normalized
+/// direction and distance to the nearest two `GravityField`s (missing
+/// fields are padded with zeros), followed by the pixel's own velocity.
+fn nearest_fields_input(position: &Vector, velocity: &Vector,
+                         fields: &[GravityField]) -> Vec<f32> {
+    let mut by_distance: Vec<&GravityField> = fields.iter().collect();
+    by_distance.sort_by(|a, b| {
+        a.position.distance(position).total_cmp(&b.position.distance(position))
+    });
+
+    let mut input = Vec::with_capacity(NN_INPUTS);
+    for field in by_distance.into_iter().take(2) {
+        let distance = field.position.distance(position);
+        let mut direction = field.position - *position;
+        direction.normalize();
+        input.push(direction.x);
+        input.push(direction.y);
+        input.push(distance);
+    }
+    while input.len() < NN_INPUTS - 2 {
+        input.push(0.);
+    }
+    input.push(velocity.x);
+    input.push(velocity.y);
+    input
+}
+
+/// Applies every peer's input for the current tick (confirmed if it has
+/// arrived, predicted otherwise), advances the simulation by one tick, and
+/// confirms the resulting state as a rollback point.
+fn advance_tick(game_field: &mut GameField, session: &mut NetplaySession) {
+    let tick = game_field.tick_count;
+    game_field.apply_remote_action(session.local_input_for(tick));
+    for peer in 0..session.num_peers() {
+        game_field.apply_remote_action(session.input_for(peer, tick));
     }
+
+    game_field.tick();
+    game_field.snake_bounds();
+    session.push_snapshot(game_field.snapshot(), 128);
+}
+
+/// Drives one fixed-timestep tick under netplay: broadcasts the local
+/// player's input, pulls in whatever remote input has arrived, and - if any
+/// of it contradicted an earlier prediction - rolls back to the earliest
+/// contradicted tick's snapshot and resimulates forward, tick by tick, back
+/// up to the present before simulating the current tick.
+fn netplay_tick(game_field: &mut GameField, session: &mut NetplaySession) {
+    // Drain whatever local presses `handle_input` accumulated since the
+    // last tick, so each press is sent (and later applied) exactly once.
+    let local_input = std::mem::take(&mut game_field.pending_input);
+
+    let target_tick = game_field.tick_count + session.input_delay() as u64;
+    let _ = session.send_local_input(target_tick, local_input);
+
+    let present_tick = game_field.tick_count;
+    if let Some(mispredicted_tick) = session.poll() {
+        if let Some(snapshot) = session.snapshot_before(mispredicted_tick).cloned() {
+            game_field.restore(&snapshot);
+        }
+        while game_field.tick_count < present_tick {
+            advance_tick(game_field, session);
+        }
+    }
+
+    advance_tick(game_field, session);
 }
 
 #[macroquad::main("Pixen")]
@@ -244,13 +639,38 @@ async fn main() {
     // Parse and create the config
     let config = GameConfig::read_config(CONFIG_NAME);
 
+    // Set up the netplay session, if configured, before handing the config
+    // off to `GameField`.
+    let mut netplay = if config.netplay.enabled {
+        Some(NetplaySession::new(config.netplay.local_port,
+                                  &config.netplay.peer_addrs,
+                                  config.netplay.input_delay)
+            .expect("Couldn't set up the netplay session."))
+    } else {
+        None
+    };
+
     // Create the game_field and start the game
     let mut game_field = GameField::new(config);
 
     '_gameloop: loop {
-        game_field.update();
-        //game_field.keep_within_bounds();
-        game_field.snake_bounds();
+        game_field.handle_input();
+
+        // Advance the simulation by as many fixed-size ticks as the elapsed
+        // render-frame time covers, so physics stays identical regardless of
+        // framerate.
+        game_field.accumulator += get_frame_time();
+        while game_field.accumulator >= game_field.config.phy.tick_rate {
+            match &mut netplay {
+                Some(session) => netplay_tick(&mut game_field, session),
+                None => {
+                    game_field.tick();
+                    game_field.snake_bounds();
+                }
+            }
+            game_field.accumulator -= game_field.config.phy.tick_rate;
+        }
+
         game_field.render();
         next_frame().await
     }
@@ -0,0 +1,204 @@
+//! Rollback netcode for sharing an arena between peers over UDP.
+//!
+//! Each peer only ever sends its own per-tick [`Input`]; every other peer's
+//! pixels, gravity fields and RNG state are reproduced locally by replaying
+//! the same deterministic simulation. Remote input for the current tick
+//! usually hasn't arrived yet, so it is predicted (repeated from the last
+//! confirmed value) and corrected later: when the real value arrives and
+//! disagrees with the prediction, the last confirmed [`Snapshot`] is
+//! restored and the simulation is re-run forward to the present tick.
+
+use std::collections::HashMap;
+use std::net::{SocketAddr, UdpSocket};
+use std::io;
+
+use crate::vector::Vector;
+use crate::gravity_field::GravityField;
+use crate::pixel::Pixel;
+use crate::rng::Rng;
+
+/// The only information a peer needs to send: its input for a single tick.
+#[derive(Copy, Clone, Default, PartialEq)]
+pub struct Input {
+    pub mouse_pos: Vector,
+    pub lmb:       bool,
+    pub rmb:       bool,
+    pub mmb:       bool,
+    pub space:     bool,
+}
+
+impl Input {
+    /// Packs this input into a fixed-size wire format: two `f32`s for the
+    /// cursor followed by one byte of button flags.
+    pub fn to_bytes(&self) -> [u8; 9] {
+        let mut buf = [0u8; 9];
+        buf[0..4].copy_from_slice(&self.mouse_pos.x.to_le_bytes());
+        buf[4..8].copy_from_slice(&self.mouse_pos.y.to_le_bytes());
+        buf[8] = (self.lmb   as u8)
+               | (self.rmb   as u8) << 1
+               | (self.mmb   as u8) << 2
+               | (self.space as u8) << 3;
+        buf
+    }
+
+    /// Unpacks an input previously packed by `to_bytes`.
+    pub fn from_bytes(buf: &[u8; 9]) -> Self {
+        Self {
+            mouse_pos: Vector::new(
+                f32::from_le_bytes(buf[0..4].try_into().unwrap()),
+                f32::from_le_bytes(buf[4..8].try_into().unwrap()),
+            ),
+            lmb:   buf[8] & 0b0001 != 0,
+            rmb:   buf[8] & 0b0010 != 0,
+            mmb:   buf[8] & 0b0100 != 0,
+            space: buf[8] & 0b1000 != 0,
+        }
+    }
+}
+
+/// An in-memory copy of the `GameField` state that rollback restores (tick,
+/// pixels, gravity fields, RNG state and pause flag), used as the point to
+/// roll back to when a prediction turns out wrong. It has no serialized
+/// form, and the agent population and its generation counter are not part
+/// of it.
+#[derive(Clone)]
+pub struct Snapshot {
+    pub tick:           u64,
+    pub pixels:         Vec<Pixel>,
+    pub gravity_fields: Vec<GravityField>,
+    pub rng:            Rng,
+    pub is_paused:      bool,
+}
+
+/// Per-peer record of which tick's input is confirmed (actually received)
+/// versus predicted (repeated from the last confirmed input).
+struct PeerInputs {
+    addr:       SocketAddr,
+    confirmed:  HashMap<u64, Input>,
+    last_known: Input,
+}
+
+/// A rollback netplay session between `num_players` peers sharing one arena.
+pub struct NetplaySession {
+    socket:      UdpSocket,
+    input_delay: u32,
+    peers:       Vec<PeerInputs>,
+    local:       HashMap<u64, Input>,
+
+    /// Ring of recent confirmed snapshots, indexed by `tick % capacity`.
+    snapshots: Vec<Option<Snapshot>>,
+}
+
+impl NetplaySession {
+    /// Binds the local socket and prepares to exchange input with `peer_addrs`.
+    pub fn new(local_port: u16, peer_addrs: &[String], input_delay: u32)
+        -> io::Result<Self>
+    {
+        let socket = UdpSocket::bind(("0.0.0.0", local_port))?;
+        socket.set_nonblocking(true)?;
+
+        let peers = peer_addrs.iter().map(|addr| PeerInputs {
+            addr:       addr.parse().expect("invalid peer address"),
+            confirmed:  HashMap::new(),
+            last_known: Input::default(),
+        }).collect();
+
+        Ok(Self {
+            socket,
+            input_delay,
+            peers,
+            local: HashMap::new(),
+            snapshots: Vec::new(),
+        })
+    }
+
+    /// Records and broadcasts the local player's input for `tick` to every peer.
+    pub fn send_local_input(&mut self, tick: u64, input: Input) -> io::Result<()> {
+        self.local.insert(tick, input);
+
+        let mut packet = Vec::with_capacity(8 + 9);
+        packet.extend_from_slice(&tick.to_le_bytes());
+        packet.extend_from_slice(&input.to_bytes());
+        for peer in &self.peers {
+            self.socket.send_to(&packet, peer.addr)?;
+        }
+        Ok(())
+    }
+
+    /// Drains any input packets that have arrived, recording them as
+    /// confirmed for their tick. Returns the earliest tick whose received
+    /// input contradicted an earlier prediction, if any: the caller must
+    /// roll back to a snapshot at or before that tick and resimulate forward
+    /// to the present, since every tick from there on was simulated with a
+    /// now-wrong guess.
+    pub fn poll(&mut self) -> Option<u64> {
+        let mut mispredicted_tick: Option<u64> = None;
+        let mut buf = [0u8; 17];
+
+        while let Ok((len, from)) = self.socket.recv_from(&mut buf) {
+            if len != buf.len() {
+                continue;
+            }
+            let Some(peer) = self.peers.iter_mut().find(|p| p.addr == from) else {
+                continue;
+            };
+
+            let tick  = u64::from_le_bytes(buf[0..8].try_into().unwrap());
+            let input = Input::from_bytes(&buf[8..17].try_into().unwrap());
+
+            let predicted = peer.confirmed.get(&tick).copied()
+                .unwrap_or(peer.last_known);
+            if predicted != input {
+                mispredicted_tick = Some(mispredicted_tick.map_or(tick, |t| t.min(tick)));
+            }
+
+            peer.confirmed.insert(tick, input);
+            peer.last_known = input;
+        }
+
+        mispredicted_tick
+    }
+
+    /// Returns the best-known input for `peer_idx` at `tick`: the confirmed
+    /// value if it has arrived, otherwise a prediction (the last confirmed
+    /// input repeated forward).
+    pub fn input_for(&self, peer_idx: usize, tick: u64) -> Input {
+        let peer = &self.peers[peer_idx];
+        peer.confirmed.get(&tick).copied().unwrap_or(peer.last_known)
+    }
+
+    /// Number of ticks local input should be delayed by before being applied,
+    /// giving remote peers a chance to receive it before it's needed.
+    pub fn input_delay(&self) -> u32 {
+        self.input_delay
+    }
+
+    /// Stores `snapshot` as the confirmed rollback point for its tick.
+    pub fn push_snapshot(&mut self, snapshot: Snapshot, capacity: usize) {
+        if self.snapshots.len() < capacity {
+            self.snapshots.resize(capacity, None);
+        }
+        let idx = (snapshot.tick as usize) % capacity;
+        self.snapshots[idx] = Some(snapshot);
+    }
+
+    /// Looks up the last confirmed snapshot at or before `tick`, the
+    /// furthest-back point a rollback can restore to.
+    pub fn snapshot_before(&self, tick: u64) -> Option<&Snapshot> {
+        self.snapshots.iter()
+            .flatten()
+            .filter(|s| s.tick <= tick)
+            .max_by_key(|s| s.tick)
+    }
+
+    /// Number of remote peers in this session.
+    pub fn num_peers(&self) -> usize {
+        self.peers.len()
+    }
+
+    /// The local player's own recorded input for `tick` (empty/default
+    /// input if it hasn't been sent yet, e.g. before `input_delay` elapses).
+    pub fn local_input_for(&self, tick: u64) -> Input {
+        self.local.get(&tick).copied().unwrap_or_default()
+    }
+}
@@ -0,0 +1,176 @@
+use crate::rng::Rng;
+
+/// Activation function applied to the hidden layers of a [`NN`]. The output
+/// layer is always left linear so it can produce unbounded steering forces.
+#[derive(Copy, Clone)]
+pub enum ActivFunc {
+    Relu,
+}
+
+impl ActivFunc {
+    fn apply(&self, x: f32) -> f32 {
+        match self {
+            ActivFunc::Relu => x.max(0.),
+        }
+    }
+}
+
+/// A weight matrix of shape `(next, prev + 1)`. The extra column folds in
+/// the bias term so the forward pass can skip a separate bias vector.
+#[derive(Clone)]
+struct Matrix {
+    rows: usize,
+    cols: usize,
+    data: Vec<f32>,
+}
+
+impl Matrix {
+    fn zeros(rows: usize, cols: usize) -> Self {
+        Self { rows, cols, data: vec![0.; rows * cols] }
+    }
+
+    fn get(&self, r: usize, c: usize) -> f32 {
+        self.data[r * self.cols + c]
+    }
+
+    /// Multiplies this matrix by `input` with an implicit bias column of
+    /// `1.0` appended, returning a vector of length `rows`.
+    fn mul_bias(&self, input: &[f32]) -> Vec<f32> {
+        let mut out = vec![0.; self.rows];
+        for r in 0..self.rows {
+            let mut sum = self.get(r, self.cols - 1); // bias
+            for c in 0..self.cols - 1 {
+                sum += self.get(r, c) * input[c];
+            }
+            out[r] = sum;
+        }
+        out
+    }
+}
+
+/// Samples a uniform float in `[0, 1)` from the game RNG.
+fn uniform01(rng: &mut Rng) -> f32 {
+    (rng.rand() % 1_000_000) as f32 / 1_000_000.
+}
+
+/// Samples from the standard normal distribution via the Box-Muller
+/// transform, built on top of the game's uniform RNG.
+fn gauss(rng: &mut Rng) -> f32 {
+    let u1 = uniform01(rng).max(f32::EPSILON);
+    let u2 = uniform01(rng);
+    (-2. * u1.ln()).sqrt() * (2. * std::f32::consts::PI * u2).cos()
+}
+
+/// A small feed-forward neural network that steers a pixel's acceleration.
+/// Rather than being trained by backpropagation, it is bred and mutated by
+/// a [`Population`] using a genetic algorithm.
+#[derive(Clone)]
+pub struct NN {
+    weights: Vec<Matrix>,
+    activ_func: ActivFunc,
+    mut_rate: f32,
+}
+
+impl NN {
+    /// Builds a new network whose layer sizes (including input and output)
+    /// are given by `config`. Every weight is initialized from the
+    /// standard-normal distribution scaled by `sqrt(2 / prev)` (He init).
+    pub fn new(config: &[usize], activ_func: ActivFunc, mut_rate: f32, rng: &mut Rng) -> Self {
+        let mut weights = Vec::with_capacity(config.len() - 1);
+        for pair in config.windows(2) {
+            let (prev, next) = (pair[0], pair[1]);
+            let scale = (2. / prev as f32).sqrt();
+            let mut mat = Matrix::zeros(next, prev + 1);
+            for v in mat.data.iter_mut() {
+                *v = gauss(rng) * scale;
+            }
+            weights.push(mat);
+        }
+        Self { weights, activ_func, mut_rate }
+    }
+
+    /// Forward-propagates `input`, applying `activ_func` to every hidden
+    /// layer and leaving the final (output) layer linear.
+    pub fn forward(&self, input: &[f32]) -> Vec<f32> {
+        let mut activations = input.to_vec();
+        let last = self.weights.len() - 1;
+        for (i, layer) in self.weights.iter().enumerate() {
+            let mut out = layer.mul_bias(&activations);
+            if i != last {
+                for v in out.iter_mut() {
+                    *v = self.activ_func.apply(*v);
+                }
+            }
+            activations = out;
+        }
+        activations
+    }
+
+    /// Clones this network, resampling each weight from the standard-normal
+    /// distribution with probability `mut_rate`.
+    fn mutate(&self, rng: &mut Rng) -> Self {
+        let mut clone = self.clone();
+        for layer in clone.weights.iter_mut() {
+            for v in layer.data.iter_mut() {
+                if uniform01(rng) < clone.mut_rate {
+                    *v = gauss(rng);
+                }
+            }
+        }
+        clone
+    }
+}
+
+/// A pool of evolving pixel brains. Generations are advanced by truncation
+/// selection: the fittest brains survive unchanged and the rest of the
+/// population is refilled by cloning and mutating them.
+pub struct Population {
+    pub brains: Vec<NN>,
+    config: Vec<usize>,
+    activ_func: ActivFunc,
+    mut_rate: f32,
+}
+
+impl Population {
+    /// Spawns `size` freshly-initialized brains, each shaped by `config`.
+    pub fn new(size: usize, config: Vec<usize>, activ_func: ActivFunc,
+               mut_rate: f32, rng: &mut Rng) -> Self {
+        let brains = (0..size)
+            .map(|_| NN::new(&config, activ_func, mut_rate, rng))
+            .collect();
+        Self { brains, config, activ_func, mut_rate }
+    }
+
+    /// Breeds the next generation from `fitness` scores (same order and
+    /// length as `brains`): keeps the top `survivors` brains as-is, then
+    /// refills the rest of the population with mutated clones of them.
+    pub fn evolve(&mut self, fitness: &[f32], survivors: usize, rng: &mut Rng) {
+        assert_eq!(fitness.len(), self.brains.len());
+        let survivors = survivors.clamp(1, self.brains.len());
+
+        let mut ranked: Vec<usize> = (0..self.brains.len()).collect();
+        ranked.sort_by(|&a, &b| fitness[b].partial_cmp(&fitness[a]).unwrap());
+
+        let elite: Vec<NN> = ranked.iter()
+            .take(survivors)
+            .map(|&i| self.brains[i].clone())
+            .collect();
+
+        let mut next_gen = elite.clone();
+        while next_gen.len() < self.brains.len() {
+            let parent = &elite[(rng.rand() as usize) % elite.len()];
+            next_gen.push(parent.mutate(rng));
+        }
+
+        self.brains = next_gen;
+    }
+
+    /// Re-creates the population from scratch, e.g. after a config reload.
+    #[allow(dead_code)]
+    pub fn reset(&mut self, rng: &mut Rng) {
+        let size = self.brains.len();
+        self.brains = (0..size)
+            .map(|_| NN::new(&self.config, self.activ_func, self.mut_rate, rng))
+            .collect();
+    }
+}
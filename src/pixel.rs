@@ -1,5 +1,6 @@
 use crate::vector::*;
 
+#[derive(Clone)]
 /// A pixel unit. This is the main protagonist of our game :)
 pub struct Pixel {
     /// The screen position vector of the pixel
@@ -7,6 +8,18 @@ pub struct Pixel {
 
     /// Current velocity of this pixel
     pub velocity: Vector,
+
+    /// Index into the `GameField`'s `Population::brains` driving this
+    /// pixel's acceleration, if agent mode is enabled.
+    pub brain: Option<usize>,
+
+    /// Accumulated fitness score of this pixel's brain over the current
+    /// generation. Reset to `0.` at the start of every generation.
+    pub fitness: f32,
+
+    /// This pixel's depth, scaling its rendered `pixel_size` the same way a
+    /// background star's size scales with depth. `1.0` means no scaling.
+    pub depth: f32,
 }
 
 impl Pixel {
@@ -15,6 +28,21 @@ impl Pixel {
         Self {
             velocity: Vector::from(0.),
             position,
+            brain: None,
+            fitness: 0.,
+            depth: 1.,
         }
     }
+
+    /// Spawns a new pixel steered by the brain at index `brain` of the
+    /// active `Population`.
+    pub fn with_brain(position: Vector, brain: usize) -> Self {
+        Self { brain: Some(brain), ..Self::new(position) }
+    }
+
+    /// Sets this pixel's depth, scaling how big it renders.
+    pub fn with_depth(mut self, depth: f32) -> Self {
+        self.depth = depth;
+        self
+    }
 }
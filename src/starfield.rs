@@ -0,0 +1,63 @@
+use macroquad::prelude::*;
+
+use crate::vector::Vector;
+use crate::rng::Rng;
+
+/// A single background star, fixed at a depth that never changes once
+/// generated.
+struct Star {
+    /// Position with no parallax applied yet.
+    base_position: Vector,
+
+    /// How far back this star sits. Higher depth means farther away, so it
+    /// renders smaller, dimmer, and shifts less under parallax.
+    depth: f32,
+
+    /// Base dot size before depth scaling.
+    size: f32,
+}
+
+/// A parallax background layer of stars rendered behind the pixels, giving
+/// the flat 2D arena a sense of volume.
+pub struct Starfield {
+    stars: Vec<Star>,
+}
+
+impl Starfield {
+    /// Scatters `count` stars uniformly over a `width`x`height` area, each
+    /// with a depth drawn from `[z_min, z_max]` and a base size from
+    /// `[size_min, size_max]`.
+    pub fn new(count: usize, z_min: f32, z_max: f32, size_min: f32, size_max: f32,
+               width: f32, height: f32, rng: &mut Rng) -> Self {
+        let stars = (0..count).map(|_| {
+            let x = (rng.rand() % width  as u64) as f32;
+            let y = (rng.rand() % height as u64) as f32;
+
+            let t_depth = rng.range(0, 1000) as f32 / 1000.;
+            let t_size  = rng.range(0, 1000) as f32 / 1000.;
+
+            Star {
+                base_position: Vector::new(x, y),
+                depth:         z_min + t_depth * (z_max - z_min),
+                size:          size_min + t_size * (size_max - size_min),
+            }
+        }).collect();
+
+        Self { stars }
+    }
+
+    /// Draws every star as a dim dot whose size and brightness scale
+    /// inversely with depth, offset by `view_offset` scaled down by depth so
+    /// closer stars shift more than distant ones as the view moves.
+    pub fn render(&self, view_offset: Vector) {
+        for star in &self.stars {
+            let parallax = view_offset * Vector::from(1. / star.depth);
+            let pos      = star.base_position + parallax;
+
+            let brightness = (255. / star.depth).clamp(0., 255.) as u8;
+            let color      = Color::from_rgba(brightness, brightness, brightness, 255);
+
+            draw_circle(pos.x, pos.y, star.size / star.depth, color);
+        }
+    }
+}
@@ -1,4 +1,4 @@
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Default, PartialEq)]
 /// A generic vector
 pub struct Vector {
     pub x: f32,